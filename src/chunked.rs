@@ -0,0 +1,204 @@
+//! Chunked transfer-encoding decoder
+
+use crate::{CR_LF, LF};
+use std::io::{self, BufRead, Read};
+
+/// Decodes an HTTP/1.1 `Transfer-Encoding: chunked` body on the fly.
+///
+/// Wraps any `BufRead` and exposes the dechunked byte stream through the
+/// standard `Read` implementation, so it can be dropped in wherever a plain
+/// body reader is expected (e.g. the `ThreadSend` pipeline).
+pub struct ChunkedReader<R: BufRead> {
+    inner: R,
+    remaining: usize,
+    done: bool,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    /// Creates a new `ChunkedReader` wrapping `inner`.
+    pub fn new(inner: R) -> ChunkedReader<R> {
+        ChunkedReader {
+            inner,
+            remaining: 0,
+            done: false,
+        }
+    }
+
+    /// Reads a chunk-size line (`<hex size>[;ext]CRLF`) and returns the size.
+    fn read_chunk_size(&mut self) -> io::Result<usize> {
+        let mut line = Vec::new();
+        let len = self.inner.read_until(LF, &mut line)?;
+
+        if len == 0 {
+            return Err(unexpected_eof("while reading chunk size"));
+        }
+
+        let line = trim_crlf(&line);
+        let size_str = match line.iter().position(|&b| b == b';') {
+            Some(idx) => &line[..idx],
+            None => line,
+        };
+        let size_str = std::str::from_utf8(size_str).map_err(|_| malformed_chunk())?;
+
+        usize::from_str_radix(size_str.trim(), 16).map_err(|_| malformed_chunk())
+    }
+
+    /// Consumes the trailer headers (if any) that follow the terminating
+    /// zero-length chunk, up to and including the final blank line.
+    fn consume_trailers(&mut self) -> io::Result<()> {
+        loop {
+            let mut line = Vec::new();
+            let len = self.inner.read_until(LF, &mut line)?;
+
+            if len == 0 || line == CR_LF || line == [LF] {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the CRLF that terminates every chunk's payload.
+    fn consume_chunk_crlf(&mut self) -> io::Result<()> {
+        let mut crlf = [0; 2];
+        self.inner.read_exact(&mut crlf)?;
+
+        if &crlf != CR_LF {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing CRLF after chunk data",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn trim_crlf(line: &[u8]) -> &[u8] {
+    let mut end = line.len();
+
+    while end > 0 && (line[end - 1] == b'\n' || line[end - 1] == b'\r') {
+        end -= 1;
+    }
+
+    &line[..end]
+}
+
+fn malformed_chunk() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed chunk size")
+}
+
+fn unexpected_eof(context: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("unexpected EOF {}", context),
+    )
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done {
+            return Ok(0);
+        }
+
+        if self.remaining == 0 {
+            let size = self.read_chunk_size()?;
+
+            if size == 0 {
+                self.consume_trailers()?;
+                self.done = true;
+                return Ok(0);
+            }
+
+            self.remaining = size;
+        }
+
+        let to_read = buf.len().min(self.remaining);
+
+        if to_read == 0 {
+            return Ok(0);
+        }
+
+        let len = self.inner.read(&mut buf[..to_read])?;
+
+        if len == 0 {
+            return Err(unexpected_eof("while reading chunk data"));
+        }
+
+        self.remaining -= len;
+
+        if self.remaining == 0 {
+            self.consume_chunk_crlf()?;
+        }
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn decodes_single_chunk() {
+        let raw = b"5\r\nhello\r\n0\r\n\r\n";
+        let mut reader = ChunkedReader::new(BufReader::new(raw.as_slice()));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, b"hello");
+    }
+
+    #[test]
+    fn decodes_multiple_chunks_with_extension() {
+        let raw = b"4;ext=1\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n";
+        let mut reader = ChunkedReader::new(BufReader::new(raw.as_slice()));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, b"Wikipedia");
+    }
+
+    #[test]
+    fn decodes_trailers_after_final_chunk() {
+        let raw = b"3\r\nfoo\r\n0\r\nX-Trailer: ok\r\n\r\n";
+        let mut reader = ChunkedReader::new(BufReader::new(raw.as_slice()));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, b"foo");
+    }
+
+    #[test]
+    fn chunk_size_split_across_reads() {
+        struct SlowReader<'a> {
+            data: &'a [u8],
+        }
+
+        impl<'a> Read for SlowReader<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let len = 1.min(buf.len()).min(self.data.len());
+                buf[..len].copy_from_slice(&self.data[..len]);
+                self.data = &self.data[len..];
+                Ok(len)
+            }
+        }
+
+        let raw = b"3\r\nfoo\r\n0\r\n\r\n";
+        let mut reader = ChunkedReader::new(BufReader::with_capacity(1, SlowReader { data: raw }));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, b"foo");
+    }
+
+    #[test]
+    fn rejects_malformed_chunk_size() {
+        let raw = b"not-hex\r\n";
+        let mut reader = ChunkedReader::new(BufReader::new(raw.as_slice()));
+        let mut out = Vec::new();
+
+        assert!(reader.read_to_end(&mut out).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Transparent response body decompression
+
+use std::io::{self, Read};
+
+#[cfg(feature = "gzip")]
+use flate2::read::{DeflateDecoder, GzDecoder};
+
+#[cfg(feature = "brotli")]
+const BROTLI_BUF_SIZE: usize = 4096;
+
+/// Wraps a response body reader and transparently decompresses it according
+/// to the value of a `Content-Encoding` header.
+///
+/// Falls through to [`DecodingReader::Identity`], passing bytes through
+/// unchanged, for an empty or unrecognized encoding (or when the matching
+/// `gzip`/`brotli` feature isn't enabled), so callers never need to detect
+/// or run a decompressor themselves.
+pub enum DecodingReader<R: Read> {
+    /// Body is not compressed; bytes pass through untouched.
+    Identity(R),
+    #[cfg(feature = "gzip")]
+    Gzip(GzDecoder<R>),
+    #[cfg(feature = "gzip")]
+    Deflate(DeflateDecoder<R>),
+    #[cfg(feature = "brotli")]
+    Brotli(brotli::Decompressor<R>),
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Selects a decoder based on the value of a `Content-Encoding` header.
+    pub fn new(inner: R, content_encoding: &str) -> DecodingReader<R> {
+        match content_encoding.trim() {
+            #[cfg(feature = "gzip")]
+            "gzip" => DecodingReader::Gzip(GzDecoder::new(inner)),
+            #[cfg(feature = "gzip")]
+            "deflate" => DecodingReader::Deflate(DeflateDecoder::new(inner)),
+            #[cfg(feature = "brotli")]
+            "br" => DecodingReader::Brotli(brotli::Decompressor::new(inner, BROTLI_BUF_SIZE)),
+            _ => DecodingReader::Identity(inner),
+        }
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            DecodingReader::Identity(r) => r.read(buf),
+            #[cfg(feature = "gzip")]
+            DecodingReader::Gzip(r) => r.read(buf),
+            #[cfg(feature = "gzip")]
+            DecodingReader::Deflate(r) => r.read(buf),
+            #[cfg(feature = "brotli")]
+            DecodingReader::Brotli(r) => r.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_passes_bytes_through_unchanged() {
+        let mut reader = DecodingReader::new(b"hello".as_slice(), "");
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, b"hello");
+    }
+
+    #[test]
+    fn unrecognized_encoding_falls_back_to_identity() {
+        let mut reader = DecodingReader::new(b"hello".as_slice(), "compress");
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, b"hello");
+    }
+}
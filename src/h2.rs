@@ -0,0 +1,887 @@
+//! HTTP/2 framing and a minimal single-stream client
+//!
+//! This carries exactly one request/response exchange per connection, which
+//! is the "at minimum" scope called for when this was introduced: a frame
+//! reader/writer for the handful of frame types a single stream needs
+//! (`SETTINGS`, `HEADERS`, `DATA`, `WINDOW_UPDATE`), and just enough HPACK
+//! (RFC 7541) to encode/decode headers without Huffman coding. Huffman-coded
+//! header values are rejected with `Error` rather than silently
+//! mis-decoded — most servers can be told to skip Huffman coding, but a
+//! decoder is a natural follow-up once multiplexing several streams is
+//! worth the complexity.
+
+use crate::error::Error;
+use std::io::{self, Read, Write};
+
+const CONNECTION_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const DEFAULT_INITIAL_WINDOW: u32 = 65_535;
+
+const FRAME_DATA: u8 = 0x0;
+const FRAME_HEADERS: u8 = 0x1;
+const FRAME_SETTINGS: u8 = 0x4;
+const FRAME_WINDOW_UPDATE: u8 = 0x8;
+
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+const FLAG_ACK: u8 = 0x1;
+
+/// A single HTTP/2 frame, as read off (or about to be written to) the wire.
+struct Frame {
+    kind: u8,
+    flags: u8,
+    stream_id: u32,
+    payload: Vec<u8>,
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<Frame> {
+    let mut header = [0; 9];
+    reader.read_exact(&mut header)?;
+
+    let length = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+    let kind = header[3];
+    let flags = header[4];
+    let stream_id = u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7FFF_FFFF;
+
+    let mut payload = vec![0; length];
+    reader.read_exact(&mut payload)?;
+
+    Ok(Frame { kind, flags, stream_id, payload })
+}
+
+fn write_frame<W: Write>(writer: &mut W, kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> io::Result<()> {
+    let len = payload.len() as u32;
+    let mut header = [0; 9];
+    header[..3].copy_from_slice(&len.to_be_bytes()[1..]);
+    header[3] = kind;
+    header[4] = flags;
+    header[5..9].copy_from_slice(&(stream_id & 0x7FFF_FFFF).to_be_bytes());
+
+    writer.write_all(&header)?;
+    writer.write_all(payload)
+}
+
+/// Drives the HTTP/2 connection preface and initial `SETTINGS` exchange.
+///
+/// Sends an empty `SETTINGS` frame (accepting the peer's defaults), then
+/// reads and acknowledges frames until the peer's own `SETTINGS` frame has
+/// been seen and acked.
+fn handshake<S: Read + Write>(stream: &mut S) -> Result<(), Error> {
+    stream.write_all(CONNECTION_PREFACE).map_err(Error::IO)?;
+    write_frame(stream, FRAME_SETTINGS, 0, 0, &[]).map_err(Error::IO)?;
+
+    loop {
+        let frame = read_frame(stream).map_err(Error::IO)?;
+
+        match frame.kind {
+            FRAME_SETTINGS if frame.flags & FLAG_ACK != 0 => {
+                // Peer acknowledged our SETTINGS; nothing to do.
+            }
+            FRAME_SETTINGS => {
+                write_frame(stream, FRAME_SETTINGS, FLAG_ACK, 0, &[]).map_err(Error::IO)?;
+                return Ok(());
+            }
+            FRAME_WINDOW_UPDATE => {
+                // A WINDOW_UPDATE seen during the handshake is dropped: its
+                // increment isn't credited to the stream's `peer_window`,
+                // which starts at the default initial window.
+            }
+            _ => {
+                return Err(Error::IO(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "unexpected frame before SETTINGS handshake completed",
+                )));
+            }
+        }
+    }
+}
+
+/// An HTTP/2 client carrying a single request/response stream over one
+/// connection, negotiated via ALPN (`h2`) during the TLS handshake.
+///
+/// Exposed as plain `Read`/`Write` so it slots into the same `request::get`
+/// call path as an HTTP/1.1 `Stream`: callers still `write_all` a normal
+/// `GET /path HTTP/1.1\r\nHost: ...\r\n\r\n`-shaped request, and read back
+/// an `HTTP/2.0 200 OK\r\nName: value\r\n...\r\n\r\n` head in the same text
+/// layout as an HTTP/1.1 response. `Http2Stream` itself
+/// handles the translation to and from HEADERS/DATA frames underneath.
+///
+/// `read` only blocks on the response `HEADERS`; the body is read lazily,
+/// one `DATA` frame at a time, so a response body is never buffered in
+/// full — a caller streaming the body through a `ChunkedReader`/
+/// `DecodingReader` (or just a slow consumer) sees it arrive incrementally
+/// rather than only after the whole thing has been received.
+pub struct Http2Stream<S: Read + Write> {
+    inner: S,
+    stream_id: u32,
+    send_window: u32,
+    /// The connection-level (stream id 0) receive window, topped up
+    /// separately from `send_window`'s per-stream one: RFC 7540 §6.9.1
+    /// tracks them independently, and a peer won't send more `DATA` once
+    /// either is exhausted.
+    connection_window: u32,
+    /// How many more bytes of request-body `DATA` this stream is still
+    /// allowed to send before the peer's own flow-control window runs out,
+    /// topped up by `WINDOW_UPDATE` frames the peer sends us. This client
+    /// writes its whole request before it ever reads a frame, so in
+    /// practice a request body larger than the initial window exhausts
+    /// this and fails the write with `WriteZero` rather than silently
+    /// overrunning the peer's advertised window.
+    peer_window: u32,
+    request_head_buf: Vec<u8>,
+    request_head_sent: bool,
+    response_head: Vec<u8>,
+    head_read: usize,
+    headers_received: bool,
+    /// Body bytes received but not yet returned to the caller via `read`,
+    /// topped up one `DATA` frame at a time so a large response body is
+    /// never buffered in full.
+    body_buf: Vec<u8>,
+    stream_done: bool,
+}
+
+impl<S: Read + Write> Http2Stream<S> {
+    /// Performs the connection preface/`SETTINGS` handshake. The request
+    /// itself is sent later, the first time the caller `write`s to this
+    /// stream, by translating the HTTP/1.1-style bytes it writes into a
+    /// `HEADERS` frame (and any further writes into `DATA` frames).
+    pub fn connect(mut inner: S) -> Result<Http2Stream<S>, Error> {
+        handshake(&mut inner)?;
+
+        Ok(Http2Stream {
+            inner,
+            stream_id: 1,
+            send_window: DEFAULT_INITIAL_WINDOW,
+            connection_window: DEFAULT_INITIAL_WINDOW,
+            peer_window: DEFAULT_INITIAL_WINDOW,
+            request_head_buf: Vec::new(),
+            request_head_sent: false,
+            response_head: Vec::new(),
+            head_read: 0,
+            headers_received: false,
+            body_buf: Vec::new(),
+            stream_done: false,
+        })
+    }
+
+    /// Returns a reference to the underlying stream, e.g. to inspect or
+    /// change its read/write timeouts.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying stream, e.g. to change
+    /// its read/write timeouts.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Parses the buffered `<method> <path> HTTP/1.1\r\nName: value\r\n...\r\n\r\n`
+    /// bytes written so far into HTTP/2 pseudo-headers and regular headers,
+    /// HPACK-encodes them, and sends them as a single `HEADERS` frame.
+    fn send_request_head(&mut self) -> io::Result<()> {
+        let text = String::from_utf8_lossy(&self.request_head_buf);
+        let mut lines = text.split("\r\n");
+
+        let request_line = lines.next().unwrap_or("");
+        let mut parts = request_line.split(' ');
+        let method = parts.next().unwrap_or("GET");
+        let path = parts.next().unwrap_or("/");
+
+        let mut headers: Vec<(String, String)> = vec![
+            (":method".to_string(), method.to_string()),
+            (":path".to_string(), path.to_string()),
+            (":scheme".to_string(), "https".to_string()),
+        ];
+
+        for line in lines {
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some((name, value)) = line.split_once(": ") {
+                let name = if name.eq_ignore_ascii_case("host") {
+                    ":authority".to_string()
+                } else {
+                    name.to_ascii_lowercase()
+                };
+                headers.push((name, value.to_string()));
+            }
+        }
+
+        let header_refs: Vec<(&str, &str)> = headers.iter().map(|(n, v)| (n.as_str(), v.as_str())).collect();
+        let encoded = hpack::encode(&header_refs);
+
+        write_frame(&mut self.inner, FRAME_HEADERS, FLAG_END_HEADERS, self.stream_id, &encoded)
+    }
+
+    /// Reads frames until one belonging to this stream (`HEADERS` or
+    /// `DATA`) appears, applying any `WINDOW_UPDATE`/`SETTINGS` frames seen
+    /// along the way instead of dropping them: a `WINDOW_UPDATE` credits
+    /// `peer_window` (our budget to send `DATA`) whether it's connection-
+    /// level (stream id 0) or scoped to our own stream, since either one
+    /// grants the same client, and `SETTINGS` has nothing to act on post
+    /// handshake for a single-stream client.
+    fn next_stream_frame(&mut self) -> Result<Frame, Error> {
+        loop {
+            let frame = read_frame(&mut self.inner).map_err(Error::IO)?;
+
+            match frame.kind {
+                FRAME_WINDOW_UPDATE => {
+                    if let Some(increment) = window_update_increment(&frame.payload) {
+                        self.peer_window = self.peer_window.saturating_add(increment);
+                    }
+                }
+                FRAME_SETTINGS => {}
+                _ if frame.stream_id == self.stream_id => return Ok(frame),
+                _ => {}
+            }
+        }
+    }
+
+    /// Buffers a `DATA` frame's payload for `read` to hand back, updates
+    /// `stream_done` from its `END_STREAM` flag, and tops up our own
+    /// per-stream and connection-level receive windows once either drops
+    /// under half its starting size.
+    fn accept_data_frame(&mut self, frame: Frame) -> Result<(), Error> {
+        self.body_buf.extend_from_slice(&frame.payload);
+
+        if frame.flags & FLAG_END_STREAM != 0 {
+            self.stream_done = true;
+        }
+
+        let len = frame.payload.len() as u32;
+        self.send_window = self.send_window.saturating_sub(len);
+        self.connection_window = self.connection_window.saturating_sub(len);
+
+        if self.send_window < DEFAULT_INITIAL_WINDOW / 2 {
+            let topup = DEFAULT_INITIAL_WINDOW - self.send_window;
+            write_frame(&mut self.inner, FRAME_WINDOW_UPDATE, 0, self.stream_id, &topup.to_be_bytes())
+                .map_err(Error::IO)?;
+            self.send_window += topup;
+        }
+
+        if self.connection_window < DEFAULT_INITIAL_WINDOW / 2 {
+            let topup = DEFAULT_INITIAL_WINDOW - self.connection_window;
+            // Connection-level WINDOW_UPDATEs always carry stream id 0,
+            // per RFC 7540 §6.9.1 — the per-stream top-up above doesn't
+            // replenish this separate window.
+            write_frame(&mut self.inner, FRAME_WINDOW_UPDATE, 0, 0, &topup.to_be_bytes()).map_err(Error::IO)?;
+            self.connection_window += topup;
+        }
+
+        Ok(())
+    }
+
+    /// Reads frames until the response `HEADERS` block is fully received,
+    /// decoding it into an HTTP/1.1-style status line + headers so the
+    /// existing `read_head`-based parsing can consume it unchanged. This
+    /// stops as soon as the headers are in hand — `DATA` is read lazily, one
+    /// frame at a time,
+    /// by `receive_more_body` as `read` calls for more.
+    fn receive_headers(&mut self) -> Result<(), Error> {
+        let mut header_block = Vec::new();
+
+        loop {
+            let frame = self.next_stream_frame()?;
+
+            match frame.kind {
+                FRAME_HEADERS => {
+                    header_block.extend_from_slice(&frame.payload);
+
+                    if frame.flags & FLAG_END_STREAM != 0 {
+                        self.stream_done = true;
+                    }
+                    if frame.flags & FLAG_END_HEADERS != 0 {
+                        break;
+                    }
+                }
+                // A server that starts sending DATA before HEADERS is done
+                // would be malformed, but buffering it rather than
+                // dropping it costs nothing and keeps this client honest.
+                FRAME_DATA => self.accept_data_frame(frame)?,
+                _ => {}
+            }
+        }
+
+        self.response_head = hpack::decode(&header_block)?;
+        Ok(())
+    }
+
+    /// Reads one more `DATA` frame's worth of body into `body_buf`, or
+    /// notices the stream has ended, so `read` never has to wait for more
+    /// than a single frame before it can return bytes.
+    fn receive_more_body(&mut self) -> Result<(), Error> {
+        while self.body_buf.is_empty() && !self.stream_done {
+            let frame = self.next_stream_frame()?;
+
+            match frame.kind {
+                FRAME_DATA => self.accept_data_frame(frame)?,
+                FRAME_HEADERS => {
+                    // Trailers: this minimal client doesn't merge them
+                    // into the response head, but still honors END_STREAM.
+                    if frame.flags & FLAG_END_STREAM != 0 {
+                        self.stream_done = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes up to `buf.len()` bytes of request-body `DATA`, truncated to
+    /// whatever's left of `peer_window` — a partial write, like any other,
+    /// once the peer's flow-control budget runs out.
+    fn write_data(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let allowed = buf.len().min(self.peer_window as usize);
+
+        if allowed == 0 {
+            return Ok(0);
+        }
+
+        write_frame(&mut self.inner, FRAME_DATA, 0, self.stream_id, &buf[..allowed])?;
+        self.peer_window -= allowed as u32;
+        Ok(allowed)
+    }
+
+    /// Like `write_data`, but loops until every byte of `buf` is sent, for
+    /// call sites (like the body bytes trailing the request head in a
+    /// single `write` call) that can't surface a partial write to their
+    /// own caller.
+    fn write_all_data(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            let written = self.write_data(buf)?;
+
+            if written == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "HTTP/2 peer flow-control window exhausted writing request body",
+                ));
+            }
+
+            buf = &buf[written..];
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the 31-bit increment out of a `WINDOW_UPDATE` frame's payload.
+fn window_update_increment(payload: &[u8]) -> Option<u32> {
+    let bytes: [u8; 4] = payload.get(..4)?.try_into().ok()?;
+    Some(u32::from_be_bytes(bytes) & 0x7FFF_FFFF)
+}
+
+impl<S: Read + Write> Read for Http2Stream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.headers_received {
+            self.receive_headers().map_err(error_to_io)?;
+            self.headers_received = true;
+        }
+
+        if self.head_read < self.response_head.len() {
+            let remaining = &self.response_head[self.head_read..];
+            let len = remaining.len().min(buf.len());
+            buf[..len].copy_from_slice(&remaining[..len]);
+            self.head_read += len;
+            return Ok(len);
+        }
+
+        if self.body_buf.is_empty() {
+            self.receive_more_body().map_err(error_to_io)?;
+        }
+
+        let len = self.body_buf.len().min(buf.len());
+        buf[..len].copy_from_slice(&self.body_buf[..len]);
+        self.body_buf.drain(..len);
+        Ok(len)
+    }
+}
+
+impl<S: Read + Write> Write for Http2Stream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.request_head_sent {
+            return self.write_data(buf);
+        }
+
+        self.request_head_buf.extend_from_slice(buf);
+
+        if let Some(pos) = find_double_crlf(&self.request_head_buf) {
+            let rest = self.request_head_buf.split_off(pos);
+            self.send_request_head()?;
+            self.request_head_sent = true;
+
+            if !rest.is_empty() {
+                self.write_all_data(&rest)?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.request_head_sent {
+            write_frame(&mut self.inner, FRAME_DATA, FLAG_END_STREAM, self.stream_id, &[])?;
+        }
+
+        self.inner.flush()
+    }
+}
+
+/// Finds the end of an HTTP head (the byte offset just past the blank-line
+/// `\r\n\r\n` that terminates it), if the full head has been seen yet.
+fn find_double_crlf(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|pos| pos + 4)
+}
+
+/// Converts an `Error` into an `io::Error`, unwrapping `Error::IO` directly
+/// and falling back to a generic error for any other variant.
+fn error_to_io(e: Error) -> io::Error {
+    match e {
+        Error::IO(e) => e,
+        _ => io::Error::new(io::ErrorKind::Other, "HTTP/2 stream error"),
+    }
+}
+
+/// Minimal HPACK (RFC 7541) support: the static table plus literal header
+/// fields without indexing, encoded without Huffman coding.
+pub mod hpack {
+    use crate::error::Error;
+    use std::io;
+
+    const STATIC_TABLE: &[(&str, &str)] = &[
+        (":authority", ""),
+        (":method", "GET"),
+        (":method", "POST"),
+        (":path", "/"),
+        (":path", "/index.html"),
+        (":scheme", "http"),
+        (":scheme", "https"),
+        (":status", "200"),
+        (":status", "204"),
+        (":status", "206"),
+        (":status", "304"),
+        (":status", "400"),
+        (":status", "404"),
+        (":status", "500"),
+    ];
+
+    /// Encodes `headers` (name/value pairs) as a literal-header-field-without
+    /// -indexing HPACK block, with both name and value sent as literal,
+    /// non-Huffman-coded strings.
+    pub fn encode(headers: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        for (name, value) in headers {
+            out.push(0x00); // Literal Header Field without Indexing, new name
+            write_string(&mut out, name);
+            write_string(&mut out, value);
+        }
+
+        out
+    }
+
+    /// Length prefix for a non-Huffman-coded string: an RFC 7541 §5.1
+    /// integer with a 7-bit prefix (the 8th bit is the Huffman flag, left
+    /// unset here), followed by the string's raw bytes.
+    fn write_string(out: &mut Vec<u8>, s: &str) {
+        write_integer(out, s.len(), 0x7F);
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Encodes `value` as an RFC 7541 §5.1 integer with a `prefix_max`-bit
+    /// prefix (e.g. `0x7F` for a 7-bit prefix): if it fits in the prefix,
+    /// emits it directly; otherwise fills the prefix with 1s and continues
+    /// in 7-bit groups, each but the last with its continuation bit set.
+    fn write_integer(out: &mut Vec<u8>, value: usize, prefix_max: u8) {
+        let prefix_max = prefix_max as usize;
+
+        if value < prefix_max {
+            out.push(value as u8);
+            return;
+        }
+
+        out.push(prefix_max as u8);
+        let mut value = value - prefix_max;
+
+        while value >= 128 {
+            out.push((value % 128) as u8 | 0x80);
+            value /= 128;
+        }
+
+        out.push(value as u8);
+    }
+
+    /// Decodes an HPACK header block into an HTTP/1.1-style status line plus
+    /// `Name: value\r\n` header lines, terminated by a blank line, so the
+    /// result can be handed straight to `read_head`.
+    ///
+    /// Only indexed header fields from the static table and literal fields
+    /// without Huffman coding are supported; anything else is rejected.
+    pub fn decode(block: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut status = String::from("200");
+        let mut reason = String::from("OK");
+        let mut header_lines = Vec::new();
+        let mut pos = 0;
+
+        while pos < block.len() {
+            let byte = block[pos];
+
+            if byte & 0x80 != 0 {
+                // Indexed Header Field
+                let index = (byte & 0x7F) as usize;
+                pos += 1;
+
+                let (name, value) = STATIC_TABLE
+                    .get(index.wrapping_sub(1))
+                    .ok_or_else(|| unsupported("unknown static table index"))?;
+
+                if *name == ":status" {
+                    status = value.to_string();
+                    reason = reason_for(value);
+                } else {
+                    header_lines.push(format!("{}: {}", &name[1..], value));
+                }
+            } else {
+                // Literal Header Field (with or without indexing), new name.
+                // Indexed names and the dynamic table aren't supported, since
+                // this client never sends indexing instructions of its own.
+                if !matches!(byte, 0x00 | 0x10 | 0x40) {
+                    return Err(unsupported("indexed-name or dynamic-table HPACK representations are not supported"));
+                }
+                pos += 1;
+                let (name, new_pos) = read_string(block, pos)?;
+                pos = new_pos;
+                let (value, new_pos) = read_string(block, pos)?;
+                pos = new_pos;
+
+                if name == ":status" {
+                    status = value.clone();
+                    reason = reason_for(&value);
+                } else {
+                    header_lines.push(format!("{}: {}", name, value));
+                }
+            }
+        }
+
+        let mut out = format!("HTTP/2.0 {} {}\r\n", status, reason).into_bytes();
+        for line in header_lines {
+            out.extend_from_slice(line.as_bytes());
+            out.extend_from_slice(b"\r\n");
+        }
+        out.extend_from_slice(b"\r\n");
+
+        Ok(out)
+    }
+
+    fn read_string(block: &[u8], pos: usize) -> Result<(String, usize), Error> {
+        let len_byte = *block.get(pos).ok_or_else(|| unsupported("truncated HPACK block"))?;
+
+        if len_byte & 0x80 != 0 {
+            return Err(unsupported("Huffman-coded HPACK strings are not supported"));
+        }
+
+        let (len, start) = read_integer(block, pos, 0x7F)?;
+        let end = start + len;
+
+        let bytes = block.get(start..end).ok_or_else(|| unsupported("truncated HPACK block"))?;
+        let s = std::str::from_utf8(bytes)
+            .map_err(|_| unsupported("non-UTF-8 HPACK string"))?
+            .to_string();
+
+        Ok((s, end))
+    }
+
+    /// Decodes an RFC 7541 §5.1 integer with a `prefix_max`-bit prefix
+    /// starting at `block[pos]` (e.g. `0x7F` for a 7-bit prefix), returning
+    /// the decoded value and the position just past it. The mirror of
+    /// [`write_integer`].
+    fn read_integer(block: &[u8], pos: usize, prefix_max: u8) -> Result<(usize, usize), Error> {
+        let first = *block.get(pos).ok_or_else(|| unsupported("truncated HPACK block"))?;
+        let prefix_max = prefix_max as usize;
+        let prefix_value = (first as usize) & prefix_max;
+
+        if prefix_value < prefix_max {
+            return Ok((prefix_value, pos + 1));
+        }
+
+        let mut value = prefix_max;
+        let mut shift = 0u32;
+        let mut next = pos + 1;
+
+        loop {
+            let byte = *block.get(next).ok_or_else(|| unsupported("truncated HPACK block"))?;
+            value += ((byte & 0x7F) as usize) << shift;
+            next += 1;
+
+            if byte & 0x80 == 0 {
+                break;
+            }
+
+            shift += 7;
+        }
+
+        Ok((value, next))
+    }
+
+    fn reason_for(status: &str) -> String {
+        match status {
+            "200" => "OK",
+            "204" => "No Content",
+            "206" => "Partial Content",
+            "304" => "Not Modified",
+            "400" => "Bad Request",
+            "404" => "Not Found",
+            "500" => "Internal Server Error",
+            _ => "",
+        }
+        .to_string()
+    }
+
+    fn unsupported(msg: &str) -> Error {
+        Error::IO(io::Error::new(io::ErrorKind::InvalidData, msg))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn encodes_literal_headers() {
+            let encoded = encode(&[(":method", "GET"), ("host", "example.com")]);
+            let decoded = decode(&encoded).unwrap();
+
+            assert_eq!(
+                String::from_utf8(decoded).unwrap(),
+                "HTTP/2.0 200 OK\r\n:method: GET\r\nhost: example.com\r\n\r\n"
+            );
+        }
+
+        #[test]
+        fn decodes_indexed_status() {
+            // Indexed Header Field for `:status: 404` (static table index 13).
+            let decoded = decode(&[0x80 | 13]).unwrap();
+
+            assert_eq!(
+                String::from_utf8(decoded).unwrap(),
+                "HTTP/2.0 404 Not Found\r\n\r\n"
+            );
+        }
+
+        #[test]
+        fn rejects_huffman_coded_strings() {
+            let huffman_literal = [0x00, 0x80 | 5, b'h', b'e', b'l', b'l', b'o'];
+            assert!(decode(&huffman_literal).is_err());
+        }
+
+        #[test]
+        fn round_trips_a_header_value_over_127_bytes() {
+            // Exercises the multi-byte HPACK integer prefix: a 200-byte
+            // value no longer fits the 7-bit length prefix in one byte.
+            let long_value = "x".repeat(200);
+            let encoded = encode(&[("x-long", &long_value)]);
+            let decoded = decode(&encoded).unwrap();
+
+            assert_eq!(
+                String::from_utf8(decoded).unwrap(),
+                format!("HTTP/2.0 200 OK\r\nx-long: {}\r\n\r\n", long_value)
+            );
+        }
+
+        #[test]
+        fn round_trips_a_header_value_over_255_bytes() {
+            // A value past the first continuation byte's own capacity
+            // (127 + 127), to catch the truncate-to-u8 bug where a single
+            // byte silently wrapped instead of continuing.
+            let long_value = "y".repeat(300);
+            let encoded = encode(&[("x-long", &long_value)]);
+            let decoded = decode(&encoded).unwrap();
+
+            assert_eq!(
+                String::from_utf8(decoded).unwrap(),
+                format!("HTTP/2.0 200 OK\r\nx-long: {}\r\n\r\n", long_value)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// A fake full-duplex peer: reads come from a pre-loaded buffer of
+    /// "bytes the server sent", writes are captured for inspection, so a
+    /// test can hand-assemble a server response without a real socket.
+    struct MockPeer {
+        incoming: Cursor<Vec<u8>>,
+        written: Vec<u8>,
+    }
+
+    impl MockPeer {
+        fn new(incoming: Vec<u8>) -> MockPeer {
+            MockPeer { incoming: Cursor::new(incoming), written: Vec::new() }
+        }
+    }
+
+    impl Read for MockPeer {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.incoming.read(buf)
+        }
+    }
+
+    impl Write for MockPeer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn frame_bytes(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, kind, flags, stream_id, payload).unwrap();
+        buf
+    }
+
+    /// Reads every frame out of `bytes` (as the server would see what the
+    /// client wrote), for assertions that don't care about exact byte
+    /// offsets.
+    fn read_all_frames(bytes: &[u8]) -> Vec<Frame> {
+        let mut reader = bytes;
+        let mut frames = Vec::new();
+
+        while !reader.is_empty() {
+            frames.push(read_frame(&mut reader).unwrap());
+        }
+
+        frames
+    }
+
+    #[test]
+    fn frame_round_trips_through_write_and_read() {
+        let bytes = frame_bytes(FRAME_HEADERS, FLAG_END_HEADERS, 1, b"hello");
+        let frame = read_frame(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(frame.kind, FRAME_HEADERS);
+        assert_eq!(frame.flags, FLAG_END_HEADERS);
+        assert_eq!(frame.stream_id, 1);
+        assert_eq!(frame.payload, b"hello");
+    }
+
+    #[test]
+    fn connect_sends_preface_and_acks_servers_settings() {
+        let incoming = frame_bytes(FRAME_SETTINGS, 0, 0, &[]);
+        let mock = MockPeer::new(incoming);
+
+        let stream = Http2Stream::connect(mock).unwrap();
+
+        assert!(stream.inner.written.starts_with(CONNECTION_PREFACE));
+
+        let written_frames = read_all_frames(&stream.inner.written[CONNECTION_PREFACE.len()..]);
+        assert_eq!(written_frames[0].kind, FRAME_SETTINGS);
+        assert_eq!(written_frames[0].flags, 0);
+
+        let ack = written_frames.last().unwrap();
+        assert_eq!(ack.kind, FRAME_SETTINGS);
+        assert_eq!(ack.flags, FLAG_ACK);
+    }
+
+    #[test]
+    fn round_trips_a_request_and_a_response_over_64kb() {
+        // Bigger than DEFAULT_INITIAL_WINDOW (65,535), split across two
+        // DATA frames, so a real peer would stall without the per-stream
+        // *and* connection-level WINDOW_UPDATEs; the mock peer has every
+        // frame preloaded, so this test instead checks that a stream-0
+        // WINDOW_UPDATE was written.
+        let body_a = vec![b'a'; 40_000];
+        let body_b = vec![b'b'; 40_000];
+        let long_header_value = "v".repeat(200);
+
+        let header_block = hpack::encode(&[(":status", "200"), ("x-long", &long_header_value)]);
+
+        let mut incoming = frame_bytes(FRAME_SETTINGS, 0, 0, &[]);
+        incoming.extend(frame_bytes(FRAME_HEADERS, FLAG_END_HEADERS, 1, &header_block));
+        incoming.extend(frame_bytes(FRAME_DATA, 0, 1, &body_a));
+        incoming.extend(frame_bytes(FRAME_DATA, FLAG_END_STREAM, 1, &body_b));
+
+        let mock = MockPeer::new(incoming);
+        let mut stream = Http2Stream::connect(mock).unwrap();
+
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+        stream.flush().unwrap();
+
+        let mut out = Vec::new();
+        stream.read_to_end(&mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(&format!("HTTP/2.0 200 OK\r\nx-long: {}\r\n\r\n", long_header_value)));
+        assert_eq!(out.len() - out.find("\r\n\r\n").unwrap() - 4, 80_000);
+
+        let sent_frames = read_all_frames(&stream.inner.written[CONNECTION_PREFACE.len()..]);
+        let connection_window_updates = sent_frames
+            .iter()
+            .filter(|f| f.kind == FRAME_WINDOW_UPDATE && f.stream_id == 0)
+            .count();
+
+        assert!(
+            connection_window_updates >= 1,
+            "expected at least one connection-level (stream 0) WINDOW_UPDATE after a >64KB response body"
+        );
+    }
+
+    #[test]
+    fn read_returns_one_data_frame_at_a_time_without_buffering_the_whole_body() {
+        let header_block = hpack::encode(&[(":status", "200")]);
+
+        let mut incoming = frame_bytes(FRAME_SETTINGS, 0, 0, &[]);
+        incoming.extend(frame_bytes(FRAME_HEADERS, FLAG_END_HEADERS, 1, &header_block));
+        incoming.extend(frame_bytes(FRAME_DATA, 0, 1, &vec![b'a'; 50]));
+        incoming.extend(frame_bytes(FRAME_DATA, FLAG_END_STREAM, 1, &vec![b'b'; 50]));
+
+        let mock = MockPeer::new(incoming);
+        let mut stream = Http2Stream::connect(mock).unwrap();
+
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+        stream.flush().unwrap();
+
+        let mut head_buf = vec![0; b"HTTP/2.0 200 OK\r\n\r\n".len()];
+        stream.read_exact(&mut head_buf).unwrap();
+        assert_eq!(&head_buf, b"HTTP/2.0 200 OK\r\n\r\n");
+
+        // A buffer big enough for both DATA frames combined; if `read`
+        // eagerly buffered the whole body up front it would hand back all
+        // 100 bytes here instead of just the one frame it actually needed.
+        let mut body_buf = [0; 200];
+        let n = stream.read(&mut body_buf).unwrap();
+
+        assert_eq!(n, 50, "a single read() shouldn't buffer more than the one DATA frame it needed");
+        assert_eq!(&body_buf[..n], vec![b'a'; 50].as_slice());
+    }
+
+    #[test]
+    fn incoming_window_update_credits_the_peer_window() {
+        let increment: u32 = 1000;
+        assert_eq!(window_update_increment(&increment.to_be_bytes()), Some(1000));
+        assert_eq!(window_update_increment(&[]), None);
+    }
+
+    #[test]
+    fn write_stops_once_the_peers_flow_control_window_is_exhausted() {
+        let incoming = frame_bytes(FRAME_SETTINGS, 0, 0, &[]);
+        let mock = MockPeer::new(incoming);
+        let mut stream = Http2Stream::connect(mock).unwrap();
+
+        stream.write_all(b"POST / HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+
+        // One more byte than the peer's initial window allows.
+        let body = vec![0u8; DEFAULT_INITIAL_WINDOW as usize + 1];
+        let err = stream.write_all(&body).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+}
@@ -0,0 +1,210 @@
+//! HTTP/3 transport: a single request/response stream over a QUIC connection
+//!
+//! This is intentionally narrow in scope: one QUIC connection opens exactly
+//! one bidirectional stream, which is all `Stream::Http3` needs to carry a
+//! single HTTP/3 request and its response. There is no connection pooling,
+//! no `Alt-Svc` discovery, and no multiplexing of several requests onto one
+//! connection — those are left for a follow-up once a single request round
+//! trip is proven out.
+
+use crate::error::Error;
+use std::{
+    io::{self, Read, Write},
+    net::ToSocketAddrs,
+    time::Duration,
+};
+use tokio::runtime::{Builder, Runtime};
+
+/// Read/write timeout bookkeeping for a [`QuicStream`].
+///
+/// Factored out of `QuicStream` itself so this plain state — which has
+/// nothing to do with `quinn` or the async runtime — can be unit tested
+/// without a live QUIC connection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Timeouts {
+    read: Option<Duration>,
+    write: Option<Duration>,
+}
+
+impl Timeouts {
+    fn read(&self) -> Option<Duration> {
+        self.read
+    }
+
+    fn set_read(&mut self, dur: Option<Duration>) {
+        self.read = dur;
+    }
+
+    fn set_write(&mut self, dur: Option<Duration>) {
+        self.write = dur;
+    }
+}
+
+/// A QUIC-backed stream carrying a single HTTP/3 request/response exchange.
+///
+/// Internally this drives an async `quinn` connection from blocking `Read`
+/// and `Write` calls by parking a small current-thread Tokio runtime next to
+/// the connection; callers never see the async machinery.
+pub struct QuicStream {
+    runtime: Runtime,
+    connection: quinn::Connection,
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+    timeouts: Timeouts,
+}
+
+impl QuicStream {
+    /// Opens a QUIC connection to `host:port` and establishes the single
+    /// bidirectional stream used to carry the HTTP/3 request.
+    pub fn connect(host: &str, port: u16, connect_timeout: Option<Duration>) -> Result<QuicStream, Error> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(Error::IO)?;
+
+        let (connection, send, recv) = runtime.block_on(async {
+            let addr = (host, port)
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::AddrNotAvailable, "could not resolve address"))?;
+
+            let client_cfg = quinn::ClientConfig::try_with_platform_verifier()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            let mut endpoint = quinn::Endpoint::client("[::]:0".parse().unwrap())
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            endpoint.set_default_client_config(client_cfg);
+
+            let connecting = endpoint
+                .connect(addr, host)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+            let connection = match connect_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, connecting)
+                    .await
+                    .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "QUIC handshake timed out"))?
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?,
+                None => connecting
+                    .await
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?,
+            };
+
+            let (send, recv) = connection
+                .open_bi()
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+            Ok::<_, io::Error>((connection, send, recv))
+        }).map_err(Error::IO)?;
+
+        Ok(QuicStream {
+            runtime,
+            connection,
+            send,
+            recv,
+            timeouts: Timeouts::default(),
+        })
+    }
+
+    /// Returns the read timeout applied to each call to `read`.
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.timeouts.read()
+    }
+
+    /// Sets the read timeout applied to each call to `read`.
+    pub fn set_read_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
+        self.timeouts.set_read(dur);
+        Ok(())
+    }
+
+    /// Sets the write timeout applied to each call to `write`.
+    pub fn set_write_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
+        self.timeouts.set_write(dur);
+        Ok(())
+    }
+}
+
+impl Read for QuicStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let QuicStream { runtime, recv, timeouts, .. } = self;
+        let read_timeout = timeouts.read;
+
+        runtime.block_on(async {
+            let fut = recv.read(buf);
+
+            let read = match read_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, fut)
+                    .await
+                    .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "QUIC read timed out"))?,
+                None => fut.await,
+            };
+
+            match read.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))? {
+                Some(n) => Ok(n),
+                None => Ok(0),
+            }
+        })
+    }
+}
+
+impl Write for QuicStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let QuicStream { runtime, send, timeouts, .. } = self;
+        let write_timeout = timeouts.write;
+
+        runtime.block_on(async {
+            let fut = send.write(buf);
+
+            match write_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, fut)
+                    .await
+                    .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "QUIC write timed out"))?
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string())),
+                None => fut
+                    .await
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string())),
+            }
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // `quinn::SendStream` flushes implicitly as it sends; `finish()` (to
+        // close the send side cleanly) happens once the request body is
+        // fully written, which is out of scope for this single-stream
+        // minimal implementation.
+        let _ = &self.connection;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts_default_to_unset() {
+        let timeouts = Timeouts::default();
+
+        assert_eq!(timeouts.read(), None);
+    }
+
+    #[test]
+    fn timeouts_set_read_is_independent_of_write() {
+        let mut timeouts = Timeouts::default();
+
+        timeouts.set_read(Some(Duration::from_secs(5)));
+        timeouts.set_write(Some(Duration::from_secs(10)));
+
+        assert_eq!(timeouts.read(), Some(Duration::from_secs(5)));
+        assert_eq!(timeouts.write, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn timeouts_can_be_cleared() {
+        let mut timeouts = Timeouts::default();
+        timeouts.set_read(Some(Duration::from_secs(5)));
+
+        timeouts.set_read(None);
+
+        assert_eq!(timeouts.read(), None);
+    }
+}
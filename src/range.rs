@@ -0,0 +1,145 @@
+//! Range-request helpers for resumable and tail downloads
+//!
+//! Builds the `Range` header value for a byte-range request, and interprets
+//! the response: a server that honors the range replies `206 Partial
+//! Content` with a `Content-Range` header describing what it actually sent;
+//! a server that doesn't range-request support ignores the header and
+//! replies `200` with the full body; and a server whose resource shrank
+//! below the requested offset replies `416 Range Not Satisfiable`.
+
+use crate::error::Error;
+use std::io;
+
+/// Builds the value of a `Range` header requesting bytes starting at
+/// `start`, through `end` inclusive if given, or to the end of the resource
+/// otherwise (e.g. `bytes=<last_len>-` to poll the tail of a growing log).
+pub fn range_header(start: u64, end: Option<u64>) -> String {
+    match end {
+        Some(end) => format!("bytes={}-{}", start, end),
+        None => format!("bytes={}-", start),
+    }
+}
+
+/// How a server responded to a byte-range request.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeResponse {
+    /// The server honored the range: `206 Partial Content` with a
+    /// `Content-Range: bytes <start>-<end>/<total>` header. `total` is
+    /// `None` when the server reported the total length as `*`.
+    Partial { start: u64, end: u64, total: Option<u64> },
+    /// The server doesn't support ranges and replied `200` with the full
+    /// body; the caller should restart the download from scratch.
+    Full,
+}
+
+/// Parses a response head (as returned by [`crate::stream::read_head`]) for
+/// the status line and `Content-Range` header, validating the result
+/// against the `start` that was requested.
+///
+/// Returns `Err` for a `416 Range Not Satisfiable` response (the resource
+/// shrank below the requested offset), for a `206` response whose
+/// `Content-Range` doesn't start where requested, or for a `206` response
+/// missing `Content-Range` entirely.
+pub fn parse_range_response(head: &[u8], requested_start: u64) -> Result<RangeResponse, Error> {
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.split("\r\n");
+
+    let status_line = lines.next().unwrap_or("");
+    let status = status_line.split_whitespace().nth(1).unwrap_or("");
+
+    match status {
+        "416" => Err(invalid_data("server replied 416 Range Not Satisfiable")),
+        "206" => {
+            let content_range = lines
+                .find_map(|line| line.strip_prefix("Content-Range: ").or_else(|| line.strip_prefix("content-range: ")))
+                .ok_or_else(|| invalid_data("206 response missing Content-Range header"))?;
+
+            let (start, end, total) = parse_content_range(content_range)?;
+
+            if start != requested_start {
+                return Err(invalid_data("Content-Range start doesn't match the requested offset"));
+            }
+
+            Ok(RangeResponse::Partial { start, end, total })
+        }
+        _ => Ok(RangeResponse::Full),
+    }
+}
+
+/// Parses a `Content-Range` header value of the form `bytes <start>-<end>/<total>`,
+/// where `<total>` may be `*` for an unknown total length.
+fn parse_content_range(value: &str) -> Result<(u64, u64, Option<u64>), Error> {
+    let value = value.trim().strip_prefix("bytes ").ok_or_else(|| invalid_data("malformed Content-Range unit"))?;
+
+    let (range, total) = value.split_once('/').ok_or_else(|| invalid_data("malformed Content-Range"))?;
+    let (start, end) = range.split_once('-').ok_or_else(|| invalid_data("malformed Content-Range"))?;
+
+    let start: u64 = start.trim().parse().map_err(|_| invalid_data("malformed Content-Range start"))?;
+    let end: u64 = end.trim().parse().map_err(|_| invalid_data("malformed Content-Range end"))?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().map_err(|_| invalid_data("malformed Content-Range total"))?),
+    };
+
+    Ok((start, end, total))
+}
+
+fn invalid_data(msg: &str) -> Error {
+    Error::IO(io::Error::new(io::ErrorKind::InvalidData, msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_open_ended_range_header() {
+        assert_eq!(range_header(100, None), "bytes=100-");
+    }
+
+    #[test]
+    fn builds_bounded_range_header() {
+        assert_eq!(range_header(0, Some(499)), "bytes=0-499");
+    }
+
+    #[test]
+    fn parses_partial_content_response() {
+        let head = b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 100-199/200\r\nContent-Length: 100\r\n\r\n";
+
+        assert_eq!(
+            parse_range_response(head, 100).unwrap(),
+            RangeResponse::Partial { start: 100, end: 199, total: Some(200) }
+        );
+    }
+
+    #[test]
+    fn parses_partial_content_with_unknown_total() {
+        let head = b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 50-1023/*\r\n\r\n";
+
+        assert_eq!(
+            parse_range_response(head, 50).unwrap(),
+            RangeResponse::Partial { start: 50, end: 1023, total: None }
+        );
+    }
+
+    #[test]
+    fn falls_back_to_full_on_200() {
+        let head = b"HTTP/1.1 200 OK\r\nContent-Length: 200\r\n\r\n";
+
+        assert_eq!(parse_range_response(head, 100).unwrap(), RangeResponse::Full);
+    }
+
+    #[test]
+    fn rejects_416_range_not_satisfiable() {
+        let head = b"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */200\r\n\r\n";
+
+        assert!(parse_range_response(head, 300).is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_content_range_start() {
+        let head = b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-99/200\r\n\r\n";
+
+        assert!(parse_range_response(head, 100).is_err());
+    }
+}
@@ -1,11 +1,15 @@
 //! TCP stream
 
-use crate::{error::Error, tls, tls::Conn, uri::Uri, CR_LF, LF};
+use crate::{
+    chunked::ChunkedReader, decode::DecodingReader, error::Error, h2::Http2Stream, tls, tls::Conn, uri::Uri, CR_LF,
+    LF,
+};
 use std::{
-    io::{self, BufRead, Read, Write},
-    net::{TcpStream, ToSocketAddrs},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
     path::Path,
-    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
     time::{Duration, Instant},
 };
 
@@ -16,14 +20,33 @@ const BUF_SIZE: usize = 16 * 1000;
 pub enum Stream {
     Http(TcpStream),
     Https(Conn<TcpStream>),
+    /// A QUIC-backed HTTP/3 connection, used when `uri` opts in via an
+    /// `h3://` scheme. An `Alt-Svc` header advertising HTTP/3 does not
+    /// select it.
+    #[cfg(feature = "http3")]
+    Http3(crate::quic::QuicStream),
+    /// An HTTP/2 connection, negotiated via ALPN during `try_to_https` when
+    /// the server selects `h2`.
+    #[cfg(feature = "http2")]
+    Http2(Http2Stream<Conn<TcpStream>>),
 }
 
 impl Stream {
-    /// Opens a TCP connection to a remote host with a connection timeout (if specified).
+    /// Opens a connection to a remote host with a connection timeout (if specified).
+    ///
+    /// When the `http3` feature is enabled and `uri` opts in to HTTP/3 via an
+    /// `h3` scheme, this opens a QUIC connection instead of a TCP one;
+    /// otherwise it behaves as before and connects over TCP.
     pub fn new(uri: &Uri, connect_timeout: Option<Duration>) -> Result<Stream, Error> {
         let host = uri.host().unwrap_or("");
         let port = uri.corr_port();
 
+        #[cfg(feature = "http3")]
+        if uri.scheme() == "h3" {
+            let quic = crate::quic::QuicStream::connect(host, port, connect_timeout)?;
+            return Ok(Stream::Http3(quic));
+        }
+
         let stream = match connect_timeout {
             Some(timeout) => connect_with_timeout(host, port, timeout)?,
             None => TcpStream::connect((host, port))?,
@@ -48,34 +71,72 @@ impl Stream {
                     let host = uri.host().unwrap_or("");
                     let mut cnf = tls::Config::default();
 
+                    #[cfg(feature = "http2")]
+                    cnf.set_alpn_protocols(&[b"h2", b"http/1.1"]);
+
                     let cnf = match root_cert_file_pem {
                         Some(p) => cnf.add_root_cert_file_pem(p)?,
                         None => &mut cnf,
                     };
 
                     let stream = cnf.connect(host, http_stream)?;
+
+                    #[cfg(feature = "http2")]
+                    if stream.negotiated_alpn_protocol() == Some(b"h2".as_slice()) {
+                        return Ok(Stream::Http2(Http2Stream::connect(stream)?));
+                    }
+
                     Ok(Stream::Https(stream))
                 } else {
                     Ok(Stream::Http(http_stream))
                 }
             }
             Stream::Https(_) => Ok(stream),
+            // QUIC carries its own TLS 1.3 handshake, so an `Http3` stream
+            // is already secure by the time `Stream::new` returns it.
+            #[cfg(feature = "http3")]
+            Stream::Http3(_) => Ok(stream),
+            // The ALPN negotiation that produces an `Http2` stream happens
+            // right here in `try_to_https`, so by the time one exists it's
+            // already past this call.
+            #[cfg(feature = "http2")]
+            Stream::Http2(_) => Ok(stream),
         }
     }
 
-    /// Sets the read timeout on the underlying TCP stream.
+    /// Sets the read timeout on the underlying stream.
     pub fn set_read_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
         match self {
             Stream::Http(stream) => Ok(stream.set_read_timeout(dur)?),
             Stream::Https(conn) => Ok(conn.get_mut().set_read_timeout(dur)?),
+            #[cfg(feature = "http3")]
+            Stream::Http3(quic) => quic.set_read_timeout(dur),
+            #[cfg(feature = "http2")]
+            Stream::Http2(h2) => Ok(h2.get_mut().set_read_timeout(dur)?),
         }
     }
 
-    /// Sets the write timeout on the underlying TCP stream.
+    /// Returns the read timeout currently set on the underlying stream.
+    pub fn read_timeout(&self) -> Result<Option<Duration>, Error> {
+        match self {
+            Stream::Http(stream) => Ok(stream.read_timeout()?),
+            Stream::Https(conn) => Ok(conn.get_ref().read_timeout()?),
+            #[cfg(feature = "http3")]
+            Stream::Http3(quic) => Ok(quic.read_timeout()),
+            #[cfg(feature = "http2")]
+            Stream::Http2(h2) => Ok(h2.get_ref().read_timeout()?),
+        }
+    }
+
+    /// Sets the write timeout on the underlying stream.
     pub fn set_write_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
         match self {
             Stream::Http(stream) => Ok(stream.set_write_timeout(dur)?),
             Stream::Https(conn) => Ok(conn.get_mut().set_write_timeout(dur)?),
+            #[cfg(feature = "http3")]
+            Stream::Http3(quic) => quic.set_write_timeout(dur),
+            #[cfg(feature = "http2")]
+            Stream::Http2(h2) => Ok(h2.get_mut().set_write_timeout(dur)?),
         }
     }
 }
@@ -85,6 +146,10 @@ impl Read for Stream {
         match self {
             Stream::Http(stream) => stream.read(buf),
             Stream::Https(stream) => stream.read(buf),
+            #[cfg(feature = "http3")]
+            Stream::Http3(quic) => quic.read(buf),
+            #[cfg(feature = "http2")]
+            Stream::Http2(h2) => h2.read(buf),
         }
     }
 }
@@ -94,16 +159,67 @@ impl Write for Stream {
         match self {
             Stream::Http(stream) => stream.write(buf),
             Stream::Https(stream) => stream.write(buf),
+            #[cfg(feature = "http3")]
+            Stream::Http3(quic) => quic.write(buf),
+            #[cfg(feature = "http2")]
+            Stream::Http2(h2) => h2.write(buf),
         }
     }
     fn flush(&mut self) -> Result<(), io::Error> {
         match self {
             Stream::Http(stream) => stream.flush(),
             Stream::Https(stream) => stream.flush(),
+            #[cfg(feature = "http3")]
+            Stream::Http3(quic) => quic.flush(),
+            #[cfg(feature = "http2")]
+            Stream::Http2(h2) => h2.flush(),
         }
     }
 }
 
+/// Trait for readers that sit on top of a stream whose read timeout can be
+/// inspected and changed, such as [`Stream`] or a [`BufReader`] wrapping one.
+pub trait ReadTimeout {
+    /// Returns the read timeout currently applied to the underlying stream.
+    fn read_timeout(&self) -> Result<Option<Duration>, Error>;
+
+    /// Sets the read timeout on the underlying stream.
+    fn set_read_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error>;
+}
+
+impl ReadTimeout for Stream {
+    fn read_timeout(&self) -> Result<Option<Duration>, Error> {
+        Stream::read_timeout(self)
+    }
+
+    fn set_read_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
+        Stream::set_read_timeout(self, dur)
+    }
+}
+
+impl<R: Read + ReadTimeout> ReadTimeout for BufReader<R> {
+    fn read_timeout(&self) -> Result<Option<Duration>, Error> {
+        self.get_ref().read_timeout()
+    }
+
+    fn set_read_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
+        self.get_mut().set_read_timeout(dur)
+    }
+}
+
+// No-op `ReadTimeout` so that tests (and any other caller) can drive
+// `ThreadSend` over a plain byte slice, which has no real notion of a
+// timeout.
+impl ReadTimeout for &[u8] {
+    fn read_timeout(&self) -> Result<Option<Duration>, Error> {
+        Ok(None)
+    }
+
+    fn set_read_timeout(&mut self, _dur: Option<Duration>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
 /// Trait that allows to send data from readers to other threads
 pub trait ThreadSend {
     /// Reads `head` of the response and sends it via `sender`
@@ -111,11 +227,36 @@ pub trait ThreadSend {
 
     /// Reads all bytes until EOF and sends them via `sender`
     fn send_all(&mut self, sender: &Sender<Vec<u8>>);
+
+    /// Reads all bytes until EOF and sends them via `sender`, like
+    /// `send_all`, but bounds the *total* time spent reading by `deadline`
+    /// rather than just each individual `read` call.
+    ///
+    /// Before every `read`, the per-read timeout on the underlying stream is
+    /// recomputed from the time remaining until `deadline`. Once the
+    /// deadline is reached, or a `read` reports `WouldBlock`/`TimedOut`, this
+    /// stops and returns `Error::Timeout` instead of silently treating the
+    /// read as EOF. The stream's previous read timeout is restored before
+    /// returning, whether sending succeeded or failed.
+    fn send_all_deadline(&mut self, sender: &Sender<Vec<u8>>, deadline: Instant) -> Result<(), Error>;
+
+    /// Reads a `Transfer-Encoding: chunked` body, decoding it on the fly via
+    /// [`ChunkedReader`], and sends the decoded bytes via `sender`. A
+    /// malformed or truncated chunk stream returns `Error` rather than
+    /// silently yielding a truncated body.
+    fn send_all_chunked(&mut self, sender: &Sender<Vec<u8>>) -> Result<(), Error>;
+
+    /// Reads a body compressed with `content_encoding` (the value of a
+    /// `Content-Encoding` header), inflating it on the fly via
+    /// [`DecodingReader`], and sends the decompressed bytes via `sender`. A
+    /// corrupted compressed stream returns `Error` rather than silently
+    /// yielding a truncated body.
+    fn send_all_decoded(&mut self, sender: &Sender<Vec<u8>>, content_encoding: &str) -> Result<(), Error>;
 }
 
 impl<T> ThreadSend for T
 where
-    T: BufRead,
+    T: BufRead + ReadTimeout,
 {
     fn send_head(&mut self, sender: &Sender<Vec<u8>>) {
         let buf = read_head(self);
@@ -135,6 +276,133 @@ where
             }
         }
     }
+
+    fn send_all_deadline(&mut self, sender: &Sender<Vec<u8>>, deadline: Instant) -> Result<(), Error> {
+        let prior_timeout = self.read_timeout()?;
+
+        let result = (|| -> Result<(), Error> {
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+
+                if remaining.is_zero() {
+                    return Err(Error::Timeout(RecvTimeoutError::Timeout));
+                }
+
+                self.set_read_timeout(Some(remaining))?;
+
+                let mut buf = [0; BUF_SIZE];
+
+                match self.read(&mut buf) {
+                    Ok(0) => return Ok(()),
+                    Ok(len) => {
+                        sender.send(buf[..len].to_vec()).unwrap();
+                    }
+                    Err(e)
+                        if e.kind() == io::ErrorKind::WouldBlock
+                            || e.kind() == io::ErrorKind::TimedOut =>
+                    {
+                        return Err(Error::Timeout(RecvTimeoutError::Timeout));
+                    }
+                    Err(e) => return Err(Error::IO(e)),
+                }
+            }
+        })();
+
+        self.set_read_timeout(prior_timeout)?;
+        result
+    }
+
+    fn send_all_chunked(&mut self, sender: &Sender<Vec<u8>>) -> Result<(), Error> {
+        let mut chunked = ChunkedReader::new(self);
+
+        loop {
+            let mut buf = [0; BUF_SIZE];
+
+            match chunked.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(len) => {
+                    let filled_buf = buf[..len].to_vec();
+                    sender.send(filled_buf).unwrap();
+                }
+                Err(e) => return Err(Error::IO(e)),
+            }
+        }
+    }
+
+    fn send_all_decoded(&mut self, sender: &Sender<Vec<u8>>, content_encoding: &str) -> Result<(), Error> {
+        let mut decoder = DecodingReader::new(self, content_encoding);
+
+        loop {
+            let mut buf = [0; BUF_SIZE];
+
+            match decoder.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(len) => {
+                    let filled_buf = buf[..len].to_vec();
+                    sender.send(filled_buf).unwrap();
+                }
+                Err(e) => return Err(Error::IO(e)),
+            }
+        }
+    }
+}
+
+/// Reads a response body from `reader`, picking the right `ThreadSend`
+/// method based on `head` (as returned by [`read_head`]) instead of making
+/// the caller inspect headers and choose by hand: a `Transfer-Encoding:
+/// chunked` response is dechunked via `send_all_chunked`, a non-identity
+/// `Content-Encoding` is inflated via `send_all_decoded`, and anything else
+/// is read as-is via `send_all_deadline` so the *total* time spent reading
+/// the body still honors `deadline` even when nothing needs decoding.
+pub fn send_body<T: ThreadSend>(
+    reader: &mut T,
+    head: &[u8],
+    sender: &Sender<Vec<u8>>,
+    deadline: Instant,
+) -> Result<(), Error> {
+    let head = String::from_utf8_lossy(head);
+
+    if header_value(&head, "Transfer-Encoding").map_or(false, |v| v.eq_ignore_ascii_case("chunked")) {
+        return reader.send_all_chunked(sender);
+    }
+
+    if let Some(encoding) = header_value(&head, "Content-Encoding").filter(|v| !v.eq_ignore_ascii_case("identity")) {
+        return reader.send_all_decoded(sender, encoding);
+    }
+
+    reader.send_all_deadline(sender, deadline)
+}
+
+/// Case-insensitively looks up a header's value in a raw, `\r\n`-separated
+/// response head like the one [`read_head`] returns.
+fn header_value<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.split("\r\n").find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Trait for writers whose underlying storage can be truncated to a given
+/// length, used by [`ThreadReceive::receive_all_from`] to drop stale bytes
+/// left over from an earlier partial write.
+pub trait Truncate {
+    /// Truncates the underlying storage to `len` bytes.
+    fn truncate(&mut self, len: u64) -> Result<(), Error>;
+}
+
+impl Truncate for std::fs::File {
+    fn truncate(&mut self, len: u64) -> Result<(), Error> {
+        Ok(self.set_len(len)?)
+    }
+}
+
+// So tests (and any other caller writing into memory rather than a file)
+// can drive `receive_all_from` the same way.
+impl Truncate for io::Cursor<Vec<u8>> {
+    fn truncate(&mut self, len: u64) -> Result<(), Error> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
 }
 
 /// Trait that allows to receive data from receivers
@@ -152,6 +420,26 @@ pub trait ThreadReceive {
     /// or `deadline` is exceeded. Writes received data into this writer.
     fn receive_all_update(&mut self, receiver: &Receiver<Vec<u8>>, deadline: Instant, update: impl Fn(usize) -> ())
         -> Result<(), Error>;
+
+    /// Continues a resumable download: seeks to the offset the response
+    /// `range` actually starts at, then receives the rest like
+    /// [`ThreadReceive::receive_all`].
+    ///
+    /// A [`RangeResponse::Partial`] seeks to its `start` (which matches the
+    /// offset the caller requested, since [`crate::range::parse_range_response`]
+    /// already validated that); a [`RangeResponse::Full`] means the server
+    /// ignored the range and sent the whole body, so this seeks back to `0`
+    /// *and* truncates, rather than just seeking — otherwise a full body
+    /// shorter than what's already on disk from an earlier partial attempt
+    /// would leave stale trailing bytes behind.
+    fn receive_all_from(
+        &mut self,
+        receiver: &Receiver<Vec<u8>>,
+        deadline: Instant,
+        range: crate::range::RangeResponse,
+    ) -> Result<(), Error>
+    where
+        Self: io::Seek + Truncate;
 }
 
 impl<T> ThreadReceive for T
@@ -230,9 +518,44 @@ where
         Ok(result?)
     }
 
+    fn receive_all_from(
+        &mut self,
+        receiver: &Receiver<Vec<u8>>,
+        deadline: Instant,
+        range: crate::range::RangeResponse,
+    ) -> Result<(), Error>
+    where
+        Self: io::Seek + Truncate,
+    {
+        let offset = match range {
+            crate::range::RangeResponse::Partial { start, .. } => start,
+            crate::range::RangeResponse::Full => {
+                self.truncate(0)?;
+                0
+            }
+        };
+
+        self.seek(io::SeekFrom::Start(offset))?;
+        self.receive_all(receiver, deadline)
+    }
 }
 
+/// Delay between starting successive connection attempts in
+/// `connect_with_timeout`'s Happy Eyeballs race.
+const HAPPY_EYEBALLS_STAGGER: Duration = Duration::from_millis(250);
+
 /// Connects to the target host with a specified timeout.
+///
+/// Resolves `host`'s addresses, interleaves the IPv6 and IPv4 candidates
+/// (RFC 8305 "Happy Eyeballs" ordering), then races a `connect_timeout`
+/// attempt per address on its own thread, each one started
+/// `HAPPY_EYEBALLS_STAGGER` after the last. This way a dead address in one
+/// family doesn't make the other wait out its own connect timeout before
+/// being tried. Returns the first successful connection; `timeout` is still
+/// a hard deadline for the whole race, so a still-outstanding attempt at
+/// that point yields `ErrorKind::TimedOut` rather than waiting on it
+/// further. If every attempt fails before the deadline, returns the last
+/// error observed.
 pub fn connect_with_timeout<T, U>(host: T, port: u16, timeout: U) -> io::Result<TcpStream>
 where
     Duration: From<U>,
@@ -240,27 +563,93 @@ where
 {
     let host = host.as_ref();
     let timeout = Duration::from(timeout);
-    let addrs: Vec<_> = (host, port).to_socket_addrs()?.collect();
-    let count = addrs.len();
+    let deadline = Instant::now() + timeout;
+
+    let addrs = interleave_families((host, port).to_socket_addrs()?);
+
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("Could not resolve address for {:?}", host),
+        ));
+    }
+
+    let attempts = addrs.len();
+    let (sender, receiver) = mpsc::channel();
 
     for (idx, addr) in addrs.into_iter().enumerate() {
-        match TcpStream::connect_timeout(&addr, timeout) {
-            Ok(stream) => return Ok(stream),
-            Err(err) => match err.kind() {
-                io::ErrorKind::TimedOut => return Err(err),
-                _ => {
-                    if idx + 1 == count {
-                        return Err(err);
-                    }
-                }
-            },
-        };
+        let sender = sender.clone();
+
+        thread::spawn(move || {
+            thread::sleep(HAPPY_EYEBALLS_STAGGER * idx as u32);
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let result = if remaining.is_zero() {
+                Err(io::Error::new(io::ErrorKind::TimedOut, "connection attempt timed out"))
+            } else {
+                TcpStream::connect_timeout(&addr, remaining)
+            };
+
+            // The race may already be over by the time this send happens;
+            // nothing is listening on the other end anymore, and that's fine.
+            let _ = sender.send(result);
+        });
+    }
+
+    let mut last_err = None;
+
+    for _ in 0..attempts {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        match receiver.recv_timeout(remaining) {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(err)) => last_err = Some(err),
+            // The deadline elapsed with attempts still outstanding: that's a
+            // genuine overall timeout, not just the last attempt's own
+            // error, even if an earlier attempt already failed for another
+            // reason.
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("connection to {:?} timed out", host),
+                ))
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
     }
 
-    Err(io::Error::new(
-        io::ErrorKind::AddrNotAvailable,
-        format!("Could not resolve address for {:?}", host),
-    ))
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::TimedOut, format!("connection to {:?} timed out", host))
+    }))
+}
+
+/// Orders resolved addresses so IPv6 and IPv4 candidates alternate, per RFC
+/// 8305's "Happy Eyeballs" interleaving, instead of exhausting one family
+/// (as `to_socket_addrs` returns it) before trying the other.
+fn interleave_families(addrs: impl Iterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.partition(|addr| addr.is_ipv6());
+    let mut interleaved = Vec::with_capacity(v6.len() + v4.len());
+    let mut v6 = v6.into_iter();
+    let mut v4 = v4.into_iter();
+
+    loop {
+        match (v6.next(), v4.next()) {
+            (Some(a), Some(b)) => interleaved.extend([a, b]),
+            (Some(a), None) => {
+                interleaved.push(a);
+                interleaved.extend(v6);
+                break;
+            }
+            (None, Some(b)) => {
+                interleaved.push(b);
+                interleaved.extend(v4);
+                break;
+            }
+            (None, None) => break,
+        }
+    }
+
+    interleaved
 }
 
 /// Exexcutes a function in a loop until operation is completed or deadline is exceeded.
@@ -467,6 +856,114 @@ mod tests {
         assert_eq!(raw_head, RESPONSE);
     }
 
+    #[test]
+    fn thread_send_send_all_deadline() {
+        let (sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + TIMEOUT;
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(RESPONSE.as_slice());
+            reader.send_all_deadline(&sender, deadline).unwrap();
+        });
+
+        let raw_head = receiver.recv().unwrap();
+        assert_eq!(raw_head, RESPONSE);
+    }
+
+    #[test]
+    fn thread_send_send_all_deadline_elapsed() {
+        let (sender, _receiver) = mpsc::channel();
+        let deadline = Instant::now() - Duration::from_secs(1);
+
+        let mut reader = BufReader::new(RESPONSE.as_slice());
+        let result = reader.send_all_deadline(&sender, deadline);
+
+        assert!(matches!(result, Err(Error::Timeout(_))));
+    }
+
+    #[test]
+    fn thread_send_send_all_chunked() {
+        const CHUNKED_BODY: &[u8] = b"5\r\nhello\r\n0\r\n\r\n";
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(CHUNKED_BODY);
+            reader.send_all_chunked(&sender).unwrap();
+        });
+
+        let decoded = receiver.recv().unwrap();
+        assert_eq!(decoded, b"hello");
+    }
+
+    #[test]
+    fn thread_send_send_all_chunked_surfaces_a_malformed_chunk_as_an_error() {
+        const MALFORMED_BODY: &[u8] = b"not-hex\r\n";
+        let (sender, _receiver) = mpsc::channel();
+
+        let mut reader = BufReader::new(MALFORMED_BODY);
+        let result = reader.send_all_chunked(&sender);
+
+        assert!(matches!(result, Err(Error::IO(_))));
+    }
+
+    #[test]
+    fn thread_send_send_all_decoded_passthrough() {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(RESPONSE.as_slice());
+            reader.send_all_decoded(&sender, "identity").unwrap();
+        });
+
+        let decoded = receiver.recv().unwrap();
+        assert_eq!(decoded, RESPONSE);
+    }
+
+    #[test]
+    fn send_body_dechunks_a_chunked_response() {
+        const CHUNKED_BODY: &[u8] = b"5\r\nhello\r\n0\r\n\r\n";
+        let head = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let (sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + TIMEOUT;
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(CHUNKED_BODY);
+            send_body(&mut reader, head, &sender, deadline).unwrap();
+        });
+
+        assert_eq!(receiver.recv().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn send_body_decodes_a_content_encoded_response() {
+        // `compress` isn't `identity`, so `send_body` routes it through
+        // `send_all_decoded`, whose `DecodingReader` passes it through as-is.
+        let head = b"HTTP/1.1 200 OK\r\nContent-Encoding: compress\r\n\r\n";
+        let (sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + TIMEOUT;
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(RESPONSE.as_slice());
+            send_body(&mut reader, head, &sender, deadline).unwrap();
+        });
+
+        assert_eq!(receiver.recv().unwrap(), RESPONSE);
+    }
+
+    #[test]
+    fn send_body_falls_back_to_deadline_bound_read_for_a_plain_response() {
+        let head = b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n";
+        let (sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + TIMEOUT;
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(RESPONSE.as_slice());
+            send_body(&mut reader, head, &sender, deadline).unwrap();
+        });
+
+        assert_eq!(receiver.recv().unwrap(), RESPONSE);
+    }
+
     #[test]
     fn thread_receive_receive() {
         let (sender, receiver) = mpsc::channel();
@@ -505,6 +1002,46 @@ mod tests {
         assert_eq!(buf, RESPONSE);
     }
 
+    #[test]
+    fn thread_receive_receive_all_from_partial_seeks_to_start() {
+        use crate::range::RangeResponse;
+        use std::io::Cursor;
+
+        let (sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + TIMEOUT;
+
+        thread::spawn(move || {
+            sender.send(b" world".to_vec()).unwrap();
+        });
+
+        let mut buf = Cursor::new(b"hello".to_vec());
+        let range = RangeResponse::Partial { start: 5, end: 10, total: Some(11) };
+        buf.receive_all_from(&receiver, deadline, range).unwrap();
+
+        assert_eq!(buf.into_inner(), b"hello world");
+    }
+
+    #[test]
+    fn thread_receive_receive_all_from_full_truncates_then_rewrites() {
+        use crate::range::RangeResponse;
+        use std::io::Cursor;
+
+        let (sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + TIMEOUT;
+
+        thread::spawn(move || {
+            sender.send(b"goodbye".to_vec()).unwrap();
+        });
+
+        // A previous partial attempt left "hello world" on disk; the
+        // server ignoring our Range request and sending a shorter full
+        // body shouldn't leave any of that behind.
+        let mut buf = Cursor::new(b"hello world".to_vec());
+        buf.receive_all_from(&receiver, deadline, RangeResponse::Full).unwrap();
+
+        assert_eq!(buf.into_inner(), b"goodbye");
+    }
+
     #[ignore]
     #[test]
     fn fn_execute_with_deadline() {
@@ -550,4 +1087,36 @@ mod tests {
 
         assert_eq!(raw_head, RESPONSE_H);
     }
+
+    #[test]
+    fn fn_interleave_families_alternates_v6_and_v4() {
+        let v6_a: SocketAddr = "[::1]:80".parse().unwrap();
+        let v6_b: SocketAddr = "[::2]:80".parse().unwrap();
+        let v4_a: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let v4_b: SocketAddr = "127.0.0.2:80".parse().unwrap();
+
+        let interleaved = interleave_families(vec![v4_a, v4_b, v6_a, v6_b].into_iter());
+
+        assert_eq!(interleaved, vec![v6_a, v4_a, v6_b, v4_b]);
+    }
+
+    #[test]
+    fn fn_interleave_families_keeps_leftover_tail() {
+        let v6_a: SocketAddr = "[::1]:80".parse().unwrap();
+        let v4_a: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let v4_b: SocketAddr = "127.0.0.2:80".parse().unwrap();
+
+        let interleaved = interleave_families(vec![v4_a, v6_a, v4_b].into_iter());
+
+        assert_eq!(interleaved, vec![v6_a, v4_a, v4_b]);
+    }
+
+    #[test]
+    fn fn_connect_with_timeout_still_connects() {
+        let uri = Uri::try_from(URI).unwrap();
+        let host = uri.host().unwrap();
+
+        let result = connect_with_timeout(host, uri.corr_port(), TIMEOUT);
+        assert!(result.is_ok());
+    }
 }